@@ -0,0 +1,83 @@
+use std::{collections::HashMap, fs};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::workspace_root;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(rename = "record-opts")]
+    pub record_opts: Option<String>,
+    #[serde(rename = "replay-opts")]
+    pub replay_opts: Option<String>,
+    #[serde(rename = "gdb-opts")]
+    pub gdb_opts: Option<String>,
+    #[serde(default)]
+    pub alias: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let root = workspace_root()?;
+        for dir in root.ancestors() {
+            for candidate in [dir.join("rr.toml"), dir.join(".cargo").join("rr.toml")] {
+                if candidate.is_file() {
+                    let contents = fs::read_to_string(&candidate)
+                        .with_context(|| format!("Failed to read `{}`", candidate))?;
+                    let config = toml::from_str(&contents)
+                        .with_context(|| format!("Failed to parse `{}`", candidate))?;
+                    return Ok(config);
+                }
+            }
+        }
+        Ok(Self::default())
+    }
+}
+
+pub fn merge_opts(config_opts: Option<&str>, cli_opts: Option<&str>) -> Option<String> {
+    match (config_opts, cli_opts) {
+        (None, None) => None,
+        (Some(opts), None) | (None, Some(opts)) => Some(opts.to_owned()),
+        (Some(config), Some(cli)) => Some(format!("{} {}", config, cli)),
+    }
+}
+
+/// Config string is split on spaces into individual opts; CLI opts are repeatable
+/// and already split, so they're appended as-is.
+pub fn merge_opt_lists(config_opts: Option<&str>, cli_opts: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = config_opts.map_or_else(Vec::new, |opts| {
+        opts.split(' ').map(str::to_owned).collect()
+    });
+    merged.extend(cli_opts.iter().cloned());
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_opts_appends_cli_after_config() {
+        assert_eq!(merge_opts(None, None), None);
+        assert_eq!(merge_opts(Some("-k"), None), Some("-k".to_owned()));
+        assert_eq!(merge_opts(None, Some("-k")), Some("-k".to_owned()));
+        assert_eq!(
+            merge_opts(Some("-k"), Some("-v")),
+            Some("-k -v".to_owned())
+        );
+    }
+
+    #[test]
+    fn merge_opt_lists_splits_config_and_keeps_cli_whole() {
+        assert_eq!(merge_opt_lists(None, &[]), Vec::<String>::new());
+        assert_eq!(
+            merge_opt_lists(Some("-ex foo"), &[]),
+            vec!["-ex".to_owned(), "foo".to_owned()]
+        );
+        assert_eq!(
+            merge_opt_lists(Some("-ex foo"), &["-ex bar".to_owned()]),
+            vec!["-ex".to_owned(), "foo".to_owned(), "-ex bar".to_owned()]
+        );
+    }
+}
@@ -6,13 +6,19 @@ use std::{
 
 use anyhow::anyhow;
 
+mod config;
 mod list;
+mod meta;
 mod record;
 mod replay;
+mod watch;
 
+pub use config::{merge_opt_lists, merge_opts, Config};
 pub use list::list;
-pub use record::record;
+pub use meta::TraceMeta;
+pub use record::{record, record_as};
 pub use replay::replay;
+pub use watch::watch;
 
 pub struct Trace(Utf8PathBuf);
 
@@ -27,11 +33,16 @@ impl Trace {
     }
 
     fn name_for_bin(bin: &Utf8Path) -> anyhow::Result<Self> {
-        let root = traces_dir()?;
         let bin_name = bin
             .file_name()
             .ok_or_else(|| anyhow!("Can't get file name of bin"))?;
-        let mut dir = root.join(bin_name);
+        Self::name_for(bin_name)
+    }
+
+    /// Disambiguates with a numeric suffix (`name-1`, `name-2`, ...) if `name` is taken.
+    pub(crate) fn name_for(name: &str) -> anyhow::Result<Self> {
+        let root = traces_dir()?;
+        let mut dir = root.join(name);
 
         let mut suffix = 0;
         loop {
@@ -39,7 +50,7 @@ impl Trace {
                 break Ok(Self(dir));
             }
             suffix += 1;
-            dir.set_file_name(format!("{}-{}", bin_name, suffix));
+            dir.set_file_name(format!("{}-{}", name, suffix));
         }
     }
 
@@ -51,6 +62,21 @@ impl Trace {
         self.0.as_path()
     }
 
+    fn meta_path(&self) -> Utf8PathBuf {
+        self.0.join("meta.json")
+    }
+
+    pub fn meta(&self) -> anyhow::Result<TraceMeta> {
+        let contents = fs::read_to_string(self.meta_path())?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub(crate) fn write_meta(&self, meta: &TraceMeta) -> anyhow::Result<()> {
+        let contents = serde_json::to_string_pretty(meta)?;
+        fs::write(self.meta_path(), contents)?;
+        Ok(())
+    }
+
     pub fn set_latest(&self) -> anyhow::Result<()> {
         let root = self.0.parent().expect("Trace has parent");
         fs::write(root.join("latest"), self.name())?;
@@ -83,6 +109,11 @@ pub fn traces_dir() -> anyhow::Result<Utf8PathBuf> {
     Ok(dir)
 }
 
+pub fn workspace_root() -> anyhow::Result<Utf8PathBuf> {
+    let meta = cargo_metadata::MetadataCommand::new().no_deps().exec()?;
+    Ok(meta.workspace_root)
+}
+
 pub fn split_rr_opts(opts: Option<&str>) -> Vec<&str> {
     opts.map_or_else(Vec::new, |s| s.split(' ').collect())
 }
@@ -1,13 +1,13 @@
 use anyhow::anyhow;
 use std::fs;
 
-use crate::traces_dir;
+use crate::{traces_dir, TraceMeta};
 
-pub fn list() -> anyhow::Result<()> {
+pub fn list(json: bool) -> anyhow::Result<()> {
     let root = traces_dir()?;
     let mut items = Vec::new();
 
-    for entry in fs::read_dir(root)? {
+    for entry in fs::read_dir(&root)? {
         let entry = entry?;
         if entry.file_type()?.is_dir() {
             let name = entry.file_name();
@@ -15,15 +15,38 @@ pub fn list() -> anyhow::Result<()> {
                 .to_str()
                 .ok_or_else(|| anyhow!("Trace name not valid unicode"))?;
             let created = entry.metadata()?.created()?;
-            items.push((created, name.to_owned()));
+            let meta = read_meta(&root.join(name).join("meta.json"));
+            items.push((created, name.to_owned(), meta));
         }
     }
 
-    items.sort_by(|(a, _), (b, _)| a.cmp(&b));
+    items.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
 
-    for (_, name) in items {
-        println!("{}", name);
+    if json {
+        print_json(&items)?;
+        return Ok(());
     }
 
+    for (_, name, meta) in items {
+        match meta {
+            Some(meta) => println!("{:<20}{:<26}{}", name, meta.recorded_at, meta.command()),
+            None => println!("{}", name),
+        }
+    }
+
+    Ok(())
+}
+
+fn read_meta(path: &camino::Utf8Path) -> Option<TraceMeta> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn print_json(items: &[(std::time::SystemTime, String, Option<TraceMeta>)]) -> anyhow::Result<()> {
+    let entries: Vec<_> = items
+        .iter()
+        .map(|(_, name, meta)| serde_json::json!({ "name": name, "meta": meta }))
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&entries)?);
     Ok(())
 }
@@ -1,14 +1,14 @@
 #![warn(clippy::all, clippy::pedantic, clippy::cargo)]
 
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, fs, sync::Arc};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
 use clap::{AppSettings, Parser, Subcommand};
 use seacan::{bin, test, CompilerMessage, ExecutableArtifact, FeatureSpec, PackageSpec};
 #[allow(unused)]
 use tracing::{debug, error, info, warn};
 
-use cargo_rr::{list, record, replay, Trace};
+use cargo_rr::{list, merge_opt_lists, merge_opts, record, record_as, replay, Config, Trace};
 
 #[derive(Parser, Debug)]
 #[clap(bin_name = "cargo", about, author)]
@@ -27,10 +27,12 @@ enum Opt {
     #[clap(about = "Replay a trace")]
     Replay(ReplayOpt),
     #[clap(about = "List traces")]
-    Ls,
+    Ls(LsOpt),
+    #[clap(about = "Record a binary or example, re-recording on every source change")]
+    Watch(RunOpt),
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[clap(setting(AppSettings::TrailingVarArg))]
 #[clap(setting(AppSettings::AllowHyphenValues))]
 struct RunOpt {
@@ -48,6 +50,8 @@ struct RunOpt {
     release: bool,
     #[clap(long)]
     package: Option<String>,
+    #[clap(long, help = "Re-record whenever a source file changes")]
+    watch: bool,
     #[clap(
         help = r#"Space-separated options to pass to `rr record` (e.g `"--chaos -M"`). See `rr record -h`"#
     )]
@@ -57,7 +61,7 @@ struct RunOpt {
 }
 
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[clap(setting(AppSettings::AllowHyphenValues))]
 struct TestOpt {
     name: Option<String>,
@@ -92,6 +96,14 @@ struct TestOpt {
     release: bool,
     #[clap(long)]
     package: Option<String>,
+    #[clap(long, help = "Re-record whenever a source file changes")]
+    watch: bool,
+    #[clap(
+        long,
+        alias = "each",
+        help = "Record every matching test into its own trace, instead of selecting one interactively"
+    )]
+    all: bool,
     #[clap(
         help = r#"Space-separated options to pass to `rr record` (e.g `"--chaos -M"`). See `rr record -h`"#
     )]
@@ -109,8 +121,27 @@ struct ReplayOpt {
         help = "Space-separated options to pass to `rr replay`. See `rr replay -h`"
     )]
     rr_opts: Option<String>,
-    #[clap(long, require_equals(true), help = "Options to pass to rust-gdb")]
+    #[clap(
+        long,
+        require_equals(true),
+        help = "Option to pass to rust-gdb; repeatable"
+    )]
     gdb_opts: Vec<String>,
+    #[clap(
+        long,
+        help = "Run gdb non-interactively in batch mode, for use in CI (implied by --command/--command-file)"
+    )]
+    batch: bool,
+    #[clap(long, help = "gdb command to run in batch mode (-ex); repeatable")]
+    command: Vec<String>,
+    #[clap(long, help = "File of gdb commands (one per line) to run in batch mode")]
+    command_file: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct LsOpt {
+    #[clap(long, help = "Output format: `human` (default) or `json`")]
+    format: Option<String>,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -126,27 +157,70 @@ fn handle_opts() -> anyhow::Result<()> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
-    let OptWrapper::Opt(opt) = OptWrapper::from_args();
+    let config = Config::load()?;
+
+    let args = resolve_alias(std::env::args().collect(), &config.alias);
+    let OptWrapper::Opt(opt) = OptWrapper::from_iter(args);
 
     debug!(?opt, "Parsed options");
 
     match opt {
-        Opt::Run(opt) => {
+        Opt::Run(mut opt) => {
+            opt.rr_opts = merge_opts(config.record_opts.as_deref(), opt.rr_opts.as_deref());
             handle_run(opt)?;
         }
-        Opt::Test(opt) => {
+        Opt::Test(mut opt) => {
+            opt.rr_opts = merge_opts(config.record_opts.as_deref(), opt.rr_opts.as_deref());
             handle_test(opt)?;
         }
-        Opt::Replay(opt) => {
+        Opt::Replay(mut opt) => {
+            opt.rr_opts = merge_opts(config.replay_opts.as_deref(), opt.rr_opts.as_deref());
+            opt.gdb_opts = merge_opt_lists(config.gdb_opts.as_deref(), &opt.gdb_opts);
             handle_replay(opt)?;
         }
-        Opt::Ls => list()?,
+        Opt::Ls(opt) => {
+            let json = match opt.format.as_deref() {
+                None | Some("human") => false,
+                Some("json") => true,
+                Some(other) => {
+                    return Err(anyhow!(
+                        "Unknown format `{}`, expected `human` or `json`",
+                        other
+                    ))
+                }
+            };
+            list(json)?;
+        }
+        Opt::Watch(mut opt) => {
+            opt.watch = true;
+            opt.rr_opts = merge_opts(config.record_opts.as_deref(), opt.rr_opts.as_deref());
+            handle_run(opt)?;
+        }
     }
 
     Ok(())
 }
 
 fn handle_run(opt: RunOpt) -> anyhow::Result<()> {
+    let watch_mode = opt.watch;
+    let opt_template = opt.clone();
+
+    let trace = run_once(opt)?;
+    print_replay_howto(&trace);
+
+    if watch_mode {
+        let meta = cargo_metadata::MetadataCommand::new().no_deps().exec()?;
+        cargo_rr::watch(&meta.workspace_root, &meta.target_directory, || {
+            let trace = run_once(opt_template.clone())?;
+            print_replay_howto(&trace);
+            Ok(())
+        })?;
+    }
+
+    Ok(())
+}
+
+fn run_once(opt: RunOpt) -> anyhow::Result<Trace> {
     let package = opt.package.map_or(PackageSpec::Any, PackageSpec::Name);
 
     let features = parse_features(opt.all_features, opt.no_default_features, opt.features)?;
@@ -166,12 +240,98 @@ fn handle_run(opt: RunOpt) -> anyhow::Result<()> {
         .on_compiler_msg(on_compiler_msg)
         .compile()?;
 
-    let trace = record(artifact.executable, opt.rr_opts.as_deref(), &opt.args)?;
+    record(artifact.executable, opt.rr_opts.as_deref(), &opt.args)
+}
+
+fn handle_test(opt: TestOpt) -> anyhow::Result<()> {
+    if opt.all {
+        if opt.watch {
+            return Err(anyhow!("--all cannot be combined with --watch"));
+        }
+        return record_all_tests(opt);
+    }
+
+    let watch_mode = opt.watch;
+    let opt_template = opt.clone();
+
+    let (trace, mut pinned) = run_test_once(opt)?;
     print_replay_howto(&trace);
+
+    if watch_mode {
+        let meta = cargo_metadata::MetadataCommand::new().no_deps().exec()?;
+        cargo_rr::watch(&meta.workspace_root, &meta.target_directory, || {
+            let (trace, reselected) = record_pinned_test(opt_template.clone(), &pinned)?;
+            pinned = reselected;
+            print_replay_howto(&trace);
+            Ok(())
+        })?;
+    }
+
     Ok(())
 }
 
-fn handle_test(opt: TestOpt) -> anyhow::Result<()> {
+fn run_test_once(opt: TestOpt) -> anyhow::Result<(Trace, TestSpec)> {
+    let (rr_opts, specs) = compile_test_specs(opt)?;
+    let selected = select_test_spec(specs)?;
+    let trace = record_selected_test(&selected, rr_opts.as_deref())?;
+    Ok((trace, selected))
+}
+
+/// Re-records the same test across a `--watch` rebuild without re-prompting skim:
+/// matches `pinned` by target + test name in the freshly compiled spec list.
+fn record_pinned_test(opt: TestOpt, pinned: &TestSpec) -> anyhow::Result<(Trace, TestSpec)> {
+    let (rr_opts, specs) = compile_test_specs(opt)?;
+    let selected = specs
+        .into_iter()
+        .find(|spec| {
+            spec.artifact.target.name == pinned.artifact.target.name
+                && spec.test.name == pinned.test.name
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Test `{}::{}` no longer matches after rebuild",
+                pinned.artifact.target.name,
+                pinned.test.name
+            )
+        })?;
+    let trace = record_selected_test(&selected, rr_opts.as_deref())?;
+    Ok((trace, selected))
+}
+
+fn record_selected_test(selected: &TestSpec, rr_opts: Option<&str>) -> anyhow::Result<Trace> {
+    record(
+        selected.artifact.executable.clone(),
+        rr_opts,
+        &selected.test.run_args(),
+    )
+}
+
+fn record_all_tests(opt: TestOpt) -> anyhow::Result<()> {
+    let (rr_opts, specs) = compile_test_specs(opt)?;
+    if specs.is_empty() {
+        return Err(anyhow!("No matching test or benchmark functions"));
+    }
+
+    for spec in specs {
+        let trace = record_as(
+            &spec.test.name,
+            spec.artifact.executable.clone(),
+            rr_opts.as_deref(),
+            &spec.test.run_args(),
+        )?;
+        eprintln!(
+            "Recorded {}::{} to {}",
+            spec.artifact.target.name,
+            spec.test.name,
+            trace.name()
+        );
+    }
+
+    eprintln!("\nRun `cargo rr ls` to see recorded traces, `cargo rr replay <name>` to debug one");
+    Ok(())
+}
+
+fn compile_test_specs(opt: TestOpt) -> anyhow::Result<(Option<String>, Vec<TestSpec>)> {
     let (rr_opts, mut compiler) = configure_test_compiler(opt)?;
     eprintln!("Compiling...");
     let artifacts = compiler.on_compiler_msg(on_compiler_msg).compile()?;
@@ -181,23 +341,14 @@ fn handle_test(opt: TestOpt) -> anyhow::Result<()> {
         let tests = artifact.tests;
         let artifact = Arc::new(artifact.artifact);
         for test in tests {
-            let spec = TestSpec {
+            specs.push(TestSpec {
                 test,
                 artifact: artifact.clone(),
-            };
-            specs.push(spec);
+            });
         }
     }
 
-    let selected = select_test_spec(specs)?;
-
-    let trace = record(
-        selected.artifact.executable.clone(),
-        rr_opts.as_deref(),
-        &selected.test.run_args(),
-    )?;
-    print_replay_howto(&trace);
-    Ok(())
+    Ok((rr_opts, specs))
 }
 
 #[derive(Clone, Debug)]
@@ -355,13 +506,90 @@ fn on_compiler_msg(msg: CompilerMessage) {
 
 fn handle_replay(opt: ReplayOpt) -> anyhow::Result<()> {
     let trace = opt.trace.map_or_else(Trace::latest, |s| Trace::new(&s))?;
-    replay(trace, opt.rr_opts.as_deref(), opt.gdb_opts)?;
+
+    if let Ok(meta) = trace.meta() {
+        eprintln!("Replaying {}: {}", trace.name(), meta.command());
+    }
+
+    let batch = opt.batch || !opt.command.is_empty() || opt.command_file.is_some();
+    let mut commands = opt.command;
+    if let Some(path) = opt.command_file {
+        let contents =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read `{}`", path))?;
+        commands.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_owned),
+        );
+    }
+    let batch_commands = batch.then_some(commands);
+
+    replay(
+        trace,
+        opt.rr_opts.as_deref(),
+        &opt.gdb_opts,
+        batch_commands.as_deref(),
+    )?;
     Ok(())
 }
 
+/// Built-in subcommand names, matching the `Opt` variants lowercased. An alias can't
+/// shadow one of these, the same way cargo refuses an `[alias]` entry named `build`.
+const BUILTIN_SUBCOMMANDS: &[&str] = &["run", "test", "replay", "ls", "watch"];
+
+/// Expands a `[alias]` entry from `rr.toml` (e.g. `flaky = "test --chaos my_test"`) into
+/// the argument vector, the same way cargo expands `[alias]` entries in its own config.
+fn resolve_alias(
+    mut args: Vec<String>,
+    aliases: &std::collections::HashMap<String, String>,
+) -> Vec<String> {
+    if let Some(name) = args.get(2) {
+        if !BUILTIN_SUBCOMMANDS.contains(&name.as_str()) {
+            if let Some(expansion) = aliases.get(name) {
+                let expanded: Vec<String> =
+                    expansion.split_whitespace().map(String::from).collect();
+                args.splice(2..3, expanded);
+            }
+        }
+    }
+    args
+}
+
 fn print_replay_howto(trace: &Trace) {
     eprintln!(
         "\nTrace {} recorded.\nRun `cargo rr replay` to debug the latest trace",
         trace.name()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        std::iter::once("cargo".to_owned())
+            .chain(s.split_whitespace().map(String::from))
+            .collect()
+    }
+
+    #[test]
+    fn resolve_alias_expands_a_matching_alias() {
+        let aliases = [("flaky".to_owned(), "test --chaos my_test".to_owned())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            resolve_alias(args("rr flaky"), &aliases),
+            args("rr test --chaos my_test")
+        );
+    }
+
+    #[test]
+    fn resolve_alias_does_not_shadow_a_builtin() {
+        let aliases = [("test".to_owned(), "run --bin evil".to_owned())]
+            .into_iter()
+            .collect();
+        assert_eq!(resolve_alias(args("rr test"), &aliases), args("rr test"));
+    }
+}
@@ -0,0 +1,21 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceMeta {
+    pub bin: Utf8PathBuf,
+    pub args: Vec<String>,
+    pub rr_opts: Option<String>,
+    /// RFC 3339 UTC timestamp.
+    pub recorded_at: String,
+    pub git_commit: Option<String>,
+}
+
+impl TraceMeta {
+    pub fn command(&self) -> String {
+        std::iter::once(self.bin.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
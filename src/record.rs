@@ -1,21 +1,41 @@
 use std::process::Command;
 
 use anyhow::Context;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::Utc;
 use tracing::debug;
 
-use crate::{split_rr_opts, Trace};
+use crate::{split_rr_opts, workspace_root, Trace, TraceMeta};
 
 pub fn record(bin: Utf8PathBuf, rr_opts: Option<&str>, args: &[String]) -> anyhow::Result<Trace> {
-    debug!(?bin, ?args, "Recording");
-
     let trace = Trace::name_for_bin(&bin)?;
+    record_into(trace, bin, rr_opts, args)
+}
+
+/// Like [`record`], but names the trace from `name` instead of the binary's file name.
+pub fn record_as(
+    name: &str,
+    bin: Utf8PathBuf,
+    rr_opts: Option<&str>,
+    args: &[String],
+) -> anyhow::Result<Trace> {
+    let trace = Trace::name_for(name)?;
+    record_into(trace, bin, rr_opts, args)
+}
+
+fn record_into(
+    trace: Trace,
+    bin: Utf8PathBuf,
+    rr_opts: Option<&str>,
+    args: &[String],
+) -> anyhow::Result<Trace> {
+    debug!(?bin, ?args, "Recording");
 
     let mut cmd = Command::new("rr")
         .arg("record")
         .args(&split_rr_opts(rr_opts))
         .args(&["--output-trace-dir", trace.0.as_str()])
-        .arg(bin)
+        .arg(&bin)
         .arg("--")
         .args(args)
         .spawn()
@@ -29,5 +49,34 @@ pub fn record(bin: Utf8PathBuf, rr_opts: Option<&str>, args: &[String]) -> anyho
         println!("cargo-rr: `rr record` exited with status {}", status);
     }
 
+    // `rr record` creates the trace dir once it gets as far as actually recording -
+    // it doesn't exist beforehand (see `Trace::name_for`). If it failed before that
+    // (e.g. bad `rr_opts`), there's nowhere to write meta.json, so skip it rather
+    // than turning a failed recording into a confusing hard error.
+    if trace.dir().is_dir() {
+        let meta = TraceMeta {
+            bin,
+            args: args.to_vec(),
+            rr_opts: rr_opts.map(str::to_owned),
+            recorded_at: Utc::now().to_rfc3339(),
+            git_commit: workspace_root().ok().and_then(|root| git_commit(&root)),
+        };
+        trace.write_meta(&meta)?;
+    }
+
     Ok(trace)
 }
+
+fn git_commit(root: &Utf8Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(&["rev-parse", "HEAD"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|commit| commit.trim().to_owned())
+}
@@ -1,27 +1,54 @@
-use std::process::Command;
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
 
 use anyhow::{anyhow, Context};
 
-use crate::{split_opts, Trace};
+use crate::{split_rr_opts, Trace};
 
-pub fn replay(trace: Trace, rr_opts: Option<&str>, gdb_opts: Option<&str>) -> anyhow::Result<()> {
-    // Ignore, as gdb handles
-    ctrlc::set_handler(|| {})?;
-
-    let mut gdb_opts = split_opts(gdb_opts);
-    gdb_opts.push("--quiet");
+/// If `batch_commands` is `Some`, gdb runs non-interactively: each command is fed
+/// in with `-ex` and a failing exit status is returned as an error.
+pub fn replay(
+    trace: Trace,
+    rr_opts: Option<&str>,
+    gdb_opts: &[String],
+    batch_commands: Option<&[String]>,
+) -> anyhow::Result<()> {
+    let mut gdb_opts: Vec<&str> = gdb_opts.iter().map(String::as_str).collect();
+    if let Some(commands) = batch_commands {
+        gdb_opts.push("-batch");
+        for command in commands {
+            gdb_opts.push("-ex");
+            gdb_opts.push(command.as_str());
+        }
+    } else {
+        // Ignore, as gdb handles it in interactive mode
+        ctrlc::set_handler(|| {})?;
+        gdb_opts.push("--quiet");
+    }
 
-    let mut cmd = Command::new("rr")
-        .arg("replay")
-        .args(split_opts(rr_opts))
+    let mut cmd = Command::new("rr");
+    cmd.arg("replay")
+        .args(split_rr_opts(rr_opts))
         .args(&["-d", "rust-gdb"])
         .arg(trace.dir())
         .arg("--")
-        .args(gdb_opts)
-        .spawn()
-        .context("Failed to run rr")?;
+        .args(gdb_opts);
+
+    if batch_commands.is_some() {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+
+    let child = cmd.spawn().context("Failed to run rr")?;
+    let output = child.wait_with_output()?;
+
+    if batch_commands.is_some() {
+        io::stdout().write_all(&output.stdout)?;
+        io::stderr().write_all(&output.stderr)?;
+    }
 
-    let status = cmd.wait()?;
+    let status = output.status;
     if !status.success() {
         return Err(anyhow!(
             "cargo-rr: `rr replay` exited with status {}",
@@ -0,0 +1,41 @@
+use std::{sync::mpsc::channel, time::Duration};
+
+use camino::Utf8Path;
+use notify::{RecursiveMode, Watcher};
+use tracing::debug;
+
+/// So a single `cargo fmt` or editor save, which touches several files, only triggers once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+pub fn watch(
+    root: &Utf8Path,
+    target_dir: &Utf8Path,
+    mut on_change: impl FnMut() -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(root.as_std_path(), RecursiveMode::Recursive)?;
+
+    let is_relevant = |event: &notify::Result<notify::Event>| match event {
+        Ok(event) => event.paths.iter().any(|path| !path.starts_with(target_dir)),
+        Err(_) => false,
+    };
+
+    loop {
+        let event = match rx.recv() {
+            Ok(event) => event,
+            Err(_) => return Ok(()), // watcher dropped
+        };
+        if !is_relevant(&event) {
+            continue;
+        }
+
+        // Drain whatever else arrives within the debounce window before rebuilding.
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+        debug!("Source changed, re-recording");
+        if let Err(err) = on_change() {
+            eprintln!("cargo-rr: {:#}", err);
+        }
+    }
+}